@@ -0,0 +1,224 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+
+pub struct DotenvParser;
+
+impl DotenvParser {
+    /// Reads and parses a dotenv file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the dotenv file to read
+    ///
+    /// # Returns
+    ///
+    /// Returns the variables keyed by name, or an error if reading or parsing fails.
+    pub fn parse_file(path: &str) -> Result<BTreeMap<String, String>> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read env file from {}", path))?;
+
+        Self::parse(&content).with_context(|| format!("Failed to parse env file {}", path))
+    }
+
+    /// Parses dotenv content into a map of variables.
+    ///
+    /// Blank lines and `#` comments are ignored and an optional leading `export`
+    /// is accepted. Double-quoted values may span several lines and support the
+    /// `\"`, `\\` and `\n` escapes; single-quoted values are taken literally;
+    /// unquoted values are trimmed and end at an inline ` #` comment.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The dotenv text to parse
+    ///
+    /// # Returns
+    ///
+    /// Returns the variables keyed by name, or an error on a malformed line.
+    pub fn parse(content: &str) -> Result<BTreeMap<String, String>> {
+        let mut vars = BTreeMap::new();
+        let mut lines = content.lines().enumerate();
+
+        while let Some((index, line)) = lines.next() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let assignment = trimmed.strip_prefix("export ").unwrap_or(trimmed);
+            let (key, raw_value) = assignment
+                .split_once('=')
+                .with_context(|| format!("Line {}: expected KEY=value", index + 1))?;
+            let key = key.trim().to_string();
+            let raw_value = raw_value.trim_start();
+
+            let value = if let Some(rest) = raw_value.strip_prefix('"') {
+                let mut quoted = rest.to_string();
+                while !Self::has_closing_quote(&quoted) {
+                    let (_, next) = lines
+                        .next()
+                        .with_context(|| format!("Line {}: unterminated double quote", index + 1))?;
+                    quoted.push('\n');
+                    quoted.push_str(next);
+                }
+                Self::unescape_double_quoted(&quoted)
+            } else if let Some(rest) = raw_value.strip_prefix('\'') {
+                let end = rest
+                    .find('\'')
+                    .with_context(|| format!("Line {}: unterminated single quote", index + 1))?;
+                rest[..end].to_string()
+            } else {
+                let end = raw_value.find(" #").unwrap_or(raw_value.len());
+                raw_value[..end].trim_end().to_string()
+            };
+
+            vars.insert(key, value);
+        }
+
+        Ok(vars)
+    }
+
+    /// Returns whether a double-quoted value contains its unescaped closing quote.
+    fn has_closing_quote(quoted: &str) -> bool {
+        let mut escaped = false;
+        for c in quoted.chars() {
+            match c {
+                '\\' if !escaped => escaped = true,
+                '"' if !escaped => return true,
+                _ => escaped = false,
+            }
+        }
+        false
+    }
+
+    /// Unescapes a double-quoted value up to its closing quote.
+    fn unescape_double_quoted(quoted: &str) -> String {
+        let mut value = String::with_capacity(quoted.len());
+        let mut chars = quoted.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some(other @ ('"' | '\\')) => value.push(other),
+                    Some(other) => {
+                        value.push('\\');
+                        value.push(other);
+                    }
+                    None => value.push('\\'),
+                },
+                c => value.push(c),
+            }
+        }
+
+        value
+    }
+}
+
+/// Differences between two sets of variables.
+#[derive(Debug, Default)]
+pub struct EnvDiff {
+    pub added: Vec<(String, String)>,
+    pub removed: Vec<(String, String)>,
+    pub changed: Vec<(String, String, String)>,
+}
+
+impl EnvDiff {
+    /// Computes the keys added, removed and changed going from `old` to `new`.
+    pub fn between(old: &BTreeMap<String, String>, new: &BTreeMap<String, String>) -> Self {
+        let mut diff = Self::default();
+
+        for (key, old_value) in old {
+            match new.get(key) {
+                None => diff.removed.push((key.clone(), old_value.clone())),
+                Some(new_value) if new_value != old_value => {
+                    diff.changed.push((key.clone(), old_value.clone(), new_value.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+        for (key, new_value) in new {
+            if !old.contains_key(key) {
+                diff.added.push((key.clone(), new_value.clone()));
+            }
+        }
+
+        diff
+    }
+
+    /// Returns whether there are no differences.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn parse_skips_blank_lines_and_comments() {
+        let parsed = DotenvParser::parse("\n# comment\n  \nA=1\n").unwrap();
+        assert_eq!(parsed, vars(&[("A", "1")]));
+    }
+
+    #[test]
+    fn parse_accepts_export_prefix() {
+        let parsed = DotenvParser::parse("export A=1").unwrap();
+        assert_eq!(parsed, vars(&[("A", "1")]));
+    }
+
+    #[test]
+    fn parse_unquoted_trims_and_strips_inline_comment() {
+        let parsed = DotenvParser::parse("A =  value # note\nB=a#b").unwrap();
+        assert_eq!(parsed, vars(&[("A", "value"), ("B", "a#b")]));
+    }
+
+    #[test]
+    fn parse_single_quoted_is_literal() {
+        let parsed = DotenvParser::parse(r#"A='x \n "y" # z'"#).unwrap();
+        assert_eq!(parsed, vars(&[("A", r#"x \n "y" # z"#)]));
+    }
+
+    #[test]
+    fn parse_double_quoted_unescapes() {
+        let parsed = DotenvParser::parse(r#"A="say \"hi\"\nback\\slash \t""#).unwrap();
+        assert_eq!(parsed, vars(&[("A", "say \"hi\"\nback\\slash \\t")]));
+    }
+
+    #[test]
+    fn parse_double_quoted_spans_lines() {
+        let parsed = DotenvParser::parse("A=\"line1\nline2\"\nB=2").unwrap();
+        assert_eq!(parsed, vars(&[("A", "line1\nline2"), ("B", "2")]));
+    }
+
+    #[test]
+    fn parse_rejects_malformed_lines() {
+        assert!(DotenvParser::parse("NO_EQUALS").is_err());
+        assert!(DotenvParser::parse("A=\"open").is_err());
+        assert!(DotenvParser::parse("A='open").is_err());
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed() {
+        let old = vars(&[("KEEP", "1"), ("GONE", "2"), ("EDIT", "3")]);
+        let new = vars(&[("KEEP", "1"), ("EDIT", "4"), ("NEW", "5")]);
+        let diff = EnvDiff::between(&old, &new);
+
+        assert_eq!(diff.added, [("NEW".to_string(), "5".to_string())]);
+        assert_eq!(diff.removed, [("GONE".to_string(), "2".to_string())]);
+        assert_eq!(diff.changed, [("EDIT".to_string(), "3".to_string(), "4".to_string())]);
+        assert!(!diff.is_empty());
+    }
+
+    #[test]
+    fn diff_of_identical_sets_is_empty() {
+        let same = vars(&[("A", "1")]);
+        assert!(EnvDiff::between(&same, &same).is_empty());
+    }
+}
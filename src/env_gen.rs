@@ -1,8 +1,88 @@
 use anyhow::{Context, Result};
-use std::fs;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 
-use crate::storage::{Storage, SkateEntry};
+use crate::storage::{SetOptions, Storage, SkateEntry};
 use crate::colors::ColoredOutput;
+use crate::filter::KeyFilter;
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Separator that dashes and spaces in keys are converted to.
+const KEY_SEPARATOR: char = '_';
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub databases: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub database: String,
+    pub file: String,
+    pub entries: usize,
+}
+
+/// How restore handles keys that already exist in the target database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ConflictStrategy {
+    /// Replace the existing value with the one from the backup
+    Overwrite,
+    /// Keep the existing value and leave the backup entry unapplied
+    Skip,
+}
+
+/// Output format for generated environment files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum EnvFormat {
+    /// KEY=value lines for dotenv loaders
+    Dotenv,
+    /// Java properties, with multi-line values written as `\` continuations
+    Properties,
+    /// JSON object mapping variable names to values (compact unless `--pretty`)
+    Json,
+    /// Minimal docker-compose.yml with a single service carrying the variables
+    ComposeFile,
+}
+
+/// Format-specific settings for generated environment files.
+pub struct EnvOptions {
+    pub format: EnvFormat,
+    /// Group variables under `# --- PREFIX ---` headers (dotenv only)
+    pub group_by_prefix: bool,
+    /// Pretty-print instead of minify (json only)
+    pub pretty: bool,
+    /// Service name (compose-file only, defaults to "app")
+    pub service: Option<String>,
+    /// Service image (compose-file only, required there)
+    pub image: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ComposeFile {
+    services: BTreeMap<String, ComposeService>,
+}
+
+#[derive(Serialize)]
+struct ComposeService {
+    image: String,
+    /// `KEY=value` items; the list form keeps every value a string, where a
+    /// mapping would turn values such as `yes` or `off` into booleans.
+    environment: Vec<String>,
+}
+
+/// Output format for `export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// `export KEY='value'` lines for eval
+    Shell,
+    /// NUL-terminated `KEY=value` records for `xargs -0` and similar tools
+    Null,
+}
 
 pub struct EnvGenerator;
 
@@ -12,24 +92,43 @@ impl EnvGenerator {
     /// # Arguments
     /// 
     /// * `output_path` - The path where the .env file will be written
-    /// * `filter` - Optional prefix to filter entries by key name
+    /// * `filter` - Key filter (prefix and/or glob) applied to entries
+    /// * `options` - Output format and its format-specific settings
     /// 
     /// # Returns
     /// 
     /// Returns `Ok(())` on success, or an error if reading entries or writing file fails.
-    pub fn generate_env_file(storage: &Storage, output_path: &str, filter: Option<&str>) -> Result<()> {
+    pub fn generate_env_file(
+        storage: &Storage,
+        output_path: &str,
+        filter: &KeyFilter,
+        options: &EnvOptions,
+    ) -> Result<()> {
+        if options.group_by_prefix && options.format != EnvFormat::Dotenv {
+            anyhow::bail!("--group-by-prefix is only supported with --format dotenv");
+        }
+        if (options.service.is_some() || options.image.is_some()) && options.format != EnvFormat::ComposeFile {
+            anyhow::bail!("--service and --image are only supported with --format compose-file");
+        }
+
         let entries = storage.list(None).context("Failed to list storage entries")?;
 
-        let filtered_entries = if let Some(prefix) = filter {
-            entries
-                .into_iter()
-                .filter(|entry| entry.key.starts_with(prefix))
-                .collect()
-        } else {
-            entries
-        };
+        let filtered_entries = filter.apply(entries);
 
-        let env_content = Self::entries_to_env_format(&filtered_entries);
+        let env_content = match options.format {
+            EnvFormat::Dotenv if options.group_by_prefix => Self::entries_to_grouped_env_format(&filtered_entries),
+            EnvFormat::Dotenv => Self::entries_to_env_format(&filtered_entries),
+            EnvFormat::Properties => Self::entries_to_properties_format(&filtered_entries),
+            EnvFormat::Json => Self::entries_to_json_format(&filtered_entries, options.pretty)?,
+            EnvFormat::ComposeFile => {
+                let image = options
+                    .image
+                    .as_deref()
+                    .context("--image is required for the compose-file format")?;
+                let service = options.service.as_deref().unwrap_or("app");
+                Self::entries_to_compose_file(&filtered_entries, service, image)?
+            }
+        };
 
         fs::write(output_path, env_content)
             .with_context(|| format!("Failed to write env file to {}", output_path))?;
@@ -85,13 +184,194 @@ impl EnvGenerator {
         entries
             .iter()
             .map(|entry| {
-                let key = entry.key.to_uppercase().replace('-', "_").replace(' ', "_");
+                let key = Self::env_key(&entry.key);
                 format!("{}={}", key, Self::quote_value(&entry.value))
             })
             .collect::<Vec<_>>()
             .join("\n")
     }
 
+    /// Converts entries to environment file format, grouped by key prefix.
+    /// 
+    /// The prefix is the part of the converted key before the first `_`, the same
+    /// separator used when converting keys. Each group is introduced by a
+    /// `# --- PREFIX ---` comment, groups are separated by a blank line and sorted
+    /// alphabetically, as are the variables within them. Keys without a separator
+    /// are written first, without a header.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `entries` - Slice of SkateEntry structs to convert
+    /// 
+    /// # Returns
+    /// 
+    /// Returns a formatted string ready for writing to an .env file.
+    pub fn entries_to_grouped_env_format(entries: &[SkateEntry]) -> String {
+        let mut ungrouped = Vec::new();
+        let mut groups: BTreeMap<String, Vec<(String, &str)>> = BTreeMap::new();
+
+        for entry in entries {
+            let key = Self::env_key(&entry.key);
+            match key.split_once(KEY_SEPARATOR) {
+                Some((prefix, _)) if !prefix.is_empty() => groups
+                    .entry(prefix.to_string())
+                    .or_default()
+                    .push((key, entry.value.as_str())),
+                _ => ungrouped.push((key, entry.value.as_str())),
+            }
+        }
+
+        let render = |mut vars: Vec<(String, &str)>| {
+            vars.sort_by(|a, b| a.0.cmp(&b.0));
+            vars.iter()
+                .map(|(key, value)| format!("{}={}", key, Self::quote_value(value)))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        let mut sections = Vec::new();
+        if !ungrouped.is_empty() {
+            sections.push(render(ungrouped));
+        }
+        for (prefix, vars) in groups {
+            sections.push(format!("# --- {} ---\n{}", prefix, render(vars)));
+        }
+
+        sections.join("\n\n")
+    }
+
+    /// Converts entries to a JSON object keyed by environment variable name.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `entries` - Slice of SkateEntry structs to convert
+    /// * `pretty` - Pretty-print instead of minify
+    /// 
+    /// # Returns
+    /// 
+    /// Returns the JSON document, or an error if serialization fails.
+    pub fn entries_to_json_format(entries: &[SkateEntry], pretty: bool) -> Result<String> {
+        let vars: BTreeMap<String, &str> = entries
+            .iter()
+            .map(|entry| (Self::env_key(&entry.key), entry.value.as_str()))
+            .collect();
+
+        Self::to_json(&vars, pretty)
+    }
+
+    /// Converts entries to a minimal docker-compose.yml with a single service.
+    /// 
+    /// This is a convenience for spinning up a quick container, not a full compose
+    /// generator: the file only holds the service image and its environment. The
+    /// environment is written as a list of `KEY=value` strings so values like `yes`
+    /// stay strings, and `$` is doubled so compose does not interpolate it.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `entries` - Slice of SkateEntry structs to convert
+    /// * `service` - Name of the service
+    /// * `image` - Image the service runs
+    /// 
+    /// # Returns
+    /// 
+    /// Returns the compose file, or an error if serialization fails.
+    pub fn entries_to_compose_file(entries: &[SkateEntry], service: &str, image: &str) -> Result<String> {
+        let variables: BTreeMap<String, String> = entries
+            .iter()
+            .map(|entry| (Self::env_key(&entry.key), entry.value.replace('$', "$$")))
+            .collect();
+        let environment = variables
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+
+        let mut services = BTreeMap::new();
+        services.insert(service.to_string(), ComposeService {
+            image: image.to_string(),
+            environment,
+        });
+
+        serde_yaml::to_string(&ComposeFile { services })
+            .context("Failed to serialize compose file to YAML")
+    }
+
+    /// Serializes a value to JSON, either pretty-printed or minified.
+    pub fn to_json<T: Serialize + ?Sized>(value: &T, pretty: bool) -> Result<String> {
+        let json = if pretty {
+            serde_json::to_string_pretty(value)
+        } else {
+            serde_json::to_string(value)
+        };
+
+        json.context("Failed to serialize to JSON")
+    }
+
+    /// Converts entries to Java properties format.
+    /// 
+    /// Keys are kept as stored. Values spanning several lines are written with an
+    /// escaped `\n` followed by a `\` line continuation, so JVM loaders read back
+    /// the original newlines. Leading whitespace on continuation lines is escaped
+    /// and non-ASCII characters are written as `\uXXXX` escapes.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `entries` - Slice of SkateEntry structs to convert
+    /// 
+    /// # Returns
+    /// 
+    /// Returns a formatted string ready for writing to a .properties file.
+    pub fn entries_to_properties_format(entries: &[SkateEntry]) -> String {
+        entries
+            .iter()
+            .map(|entry| {
+                let value = entry
+                    .value
+                    .split('\n')
+                    .map(|line| Self::escape_properties(line, false))
+                    .collect::<Vec<_>>()
+                    .join("\\n\\\n    ");
+                format!("{}={}", Self::escape_properties(&entry.key, true), value)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Escapes a single line of text for a properties file.
+    /// 
+    /// Backslashes, tabs, carriage returns, leading whitespace and non-ASCII
+    /// characters are always escaped; keys additionally escape spaces and the
+    /// `=`, `:`, `#` and `!` separators.
+    fn escape_properties(text: &str, is_key: bool) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for (i, c) in text.chars().enumerate() {
+            match c {
+                '\\' => escaped.push_str("\\\\"),
+                '\t' => escaped.push_str("\\t"),
+                '\r' => escaped.push_str("\\r"),
+                ' ' if i == 0 || is_key => escaped.push_str("\\ "),
+                '=' | ':' | '#' | '!' if is_key => {
+                    escaped.push('\\');
+                    escaped.push(c);
+                }
+                c if !c.is_ascii() => {
+                    let mut units = [0u16; 2];
+                    for unit in c.encode_utf16(&mut units) {
+                        escaped.push_str(&format!("\\u{:04x}", unit));
+                    }
+                }
+                c => escaped.push(c),
+            }
+        }
+        escaped
+    }
+
+    /// Converts a key to an environment variable name.
+    /// 
+    /// Keys are uppercased and dashes and spaces are replaced by `KEY_SEPARATOR`.
+    fn env_key(key: &str) -> String {
+        key.to_uppercase().replace(['-', ' '], &KEY_SEPARATOR.to_string())
+    }
+
     /// Quotes a value if it contains special characters.
     /// 
     /// Values containing spaces, newlines, or quotes are wrapped in quotes
@@ -116,22 +396,15 @@ impl EnvGenerator {
     /// 
     /// # Arguments
     /// 
-    /// * `filter` - Optional prefix to filter entries by key name
+    /// * `filter` - Key filter (prefix and/or glob) applied to entries
     /// 
     /// # Returns
     /// 
     /// Returns `Ok(())` on success, or an error if reading entries fails.
-    pub fn show_preview(storage: &Storage, filter: Option<&str>) -> Result<()> {
+    pub fn show_preview(storage: &Storage, filter: &KeyFilter) -> Result<()> {
         let entries = storage.list(None).context("Failed to list storage entries")?;
 
-        let filtered_entries = if let Some(prefix) = filter {
-            entries
-                .into_iter()
-                .filter(|entry| entry.key.starts_with(prefix))
-                .collect()
-        } else {
-            entries
-        };
+        let filtered_entries = filter.apply(entries);
 
         if filtered_entries.is_empty() {
             println!("{}", ColoredOutput::warning("No entries found"));
@@ -141,7 +414,7 @@ impl EnvGenerator {
         println!("{}", ColoredOutput::header("Preview of environment variables:"));
         println!();
         for entry in &filtered_entries {
-            let key = entry.key.to_uppercase().replace('-', "_").replace(' ', "_");
+            let key = Self::env_key(&entry.key);
             println!("{}", ColoredOutput::format_env_line(&key, &Self::quote_value(&entry.value)));
         }
         println!();
@@ -161,32 +434,61 @@ impl EnvGenerator {
     ///
     /// * `storage` - The storage instance
     /// * `database` - Optional database name (defaults to "default")
-    /// * `filter` - Optional prefix to filter entries by key name
+    /// * `filter` - Key filter (prefix and/or glob) applied to entries
+    /// * `format` - Shell export statements or NUL-delimited records
     ///
     /// # Returns
     ///
-    /// Returns `Ok(())` on success, or an error if reading entries fails.
-    pub fn export_shell(storage: &Storage, database: Option<&str>, filter: Option<&str>) -> Result<()> {
+    /// Returns `Ok(())` on success, or an error if reading entries or writing output fails.
+    pub fn export_shell(
+        storage: &Storage,
+        database: Option<&str>,
+        filter: &KeyFilter,
+        format: ExportFormat,
+    ) -> Result<()> {
         let entries = storage.list(database).context("Failed to list storage entries")?;
 
-        let filtered_entries: Vec<_> = if let Some(prefix) = filter {
-            entries
-                .into_iter()
-                .filter(|entry| entry.key.starts_with(prefix))
-                .collect()
-        } else {
-            entries
-        };
+        let filtered_entries = filter.apply(entries);
 
-        for entry in filtered_entries {
-            let key = entry.key.to_uppercase().replace('-', "_").replace(' ', "_");
-            let escaped_value = Self::shell_escape(&entry.value);
-            println!("export {}={}", key, escaped_value);
+        match format {
+            ExportFormat::Shell => {
+                for entry in filtered_entries {
+                    let key = Self::env_key(&entry.key);
+                    let escaped_value = Self::shell_escape(&entry.value);
+                    println!("export {}={}", key, escaped_value);
+                }
+            }
+            ExportFormat::Null => {
+                let mut stdout = io::stdout().lock();
+                stdout
+                    .write_all(Self::entries_to_null_format(&filtered_entries).as_bytes())
+                    .and_then(|_| stdout.flush())
+                    .context("Failed to write NUL-delimited output")?;
+            }
         }
 
         Ok(())
     }
 
+    /// Converts entries to NUL-terminated `KEY=value` records.
+    ///
+    /// Keys are converted like in the env format, but values are written raw:
+    /// the NUL framing keeps newlines and quotes intact without any quoting.
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - Slice of SkateEntry structs to convert
+    ///
+    /// # Returns
+    ///
+    /// Returns the concatenated records, each terminated by `\0`.
+    pub fn entries_to_null_format(entries: &[SkateEntry]) -> String {
+        entries
+            .iter()
+            .map(|entry| format!("{}={}\0", Self::env_key(&entry.key), entry.value))
+            .collect()
+    }
+
     /// Escapes a value for safe shell evaluation.
     ///
     /// Uses single quotes for safety and escapes any single quotes in the value.
@@ -202,18 +504,25 @@ impl EnvGenerator {
         format!("'{}'", value.replace('\'', "'\\''"))
     }
 
-    /// Creates a JSON backup of all entries.
+    /// Creates a JSON backup of all entries in a database.
     /// 
     /// # Arguments
     /// 
+    /// * `database` - Optional database name (defaults to "default")
     /// * `output_path` - The path where the backup file will be written
+    /// * `pretty` - Pretty-print instead of minify
     /// 
     /// # Returns
     /// 
-    /// Returns `Ok(())` on success, or an error if reading entries or writing file fails.
-    pub fn backup_to_file(storage: &Storage, output_path: &str) -> Result<()> {
-        let entries = storage.list(None).context("Failed to list storage entries")?;
-        let json = serde_json::to_string_pretty(&entries)
+    /// Returns the number of entries backed up, or an error if reading entries or writing file fails.
+    pub fn backup_to_file(
+        storage: &Storage,
+        database: Option<&str>,
+        output_path: &str,
+        pretty: bool,
+    ) -> Result<usize> {
+        let entries = storage.list(database).context("Failed to list storage entries")?;
+        let json = Self::to_json(&entries, pretty)
             .context("Failed to serialize entries to JSON")?;
 
         fs::write(output_path, json)
@@ -224,37 +533,474 @@ impl EnvGenerator {
             ColoredOutput::count(entries.len()),
             ColoredOutput::path(output_path)
         );
+        Ok(entries.len())
+    }
+
+    /// Backs up every database to its own `<db>.json` file in a directory.
+    ///
+    /// A `manifest.json` listing each database, its file and entry count is
+    /// written alongside the backups so they can be restored selectively. A
+    /// database named `manifest` is rejected since its file would clash with it.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory where backup files will be written (created if missing)
+    /// * `pretty` - Pretty-print the backup files instead of minifying them
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error if any database or file operation fails.
+    pub fn backup_all_to_dir(storage: &Storage, dir: &str, pretty: bool) -> Result<()> {
+        let databases = storage.list_databases()?;
+        if databases.iter().any(|db| format!("{}.json", db) == MANIFEST_FILE) {
+            anyhow::bail!(
+                "Database '{}' would be overwritten by {}; rename it before running backup-all",
+                MANIFEST_FILE.trim_end_matches(".json"),
+                MANIFEST_FILE
+            );
+        }
+
+        let dir_path = Path::new(dir);
+        fs::create_dir_all(dir_path)
+            .with_context(|| format!("Failed to create backup directory {}", dir))?;
+
+        let mut manifest = BackupManifest { databases: Vec::new() };
+        let mut total_entries = 0;
+
+        for db in databases {
+            let file = format!("{}.json", db);
+            let output_path = dir_path.join(&file);
+            let entries = Self::backup_to_file(storage, Some(&db), &output_path.to_string_lossy(), pretty)?;
+
+            total_entries += entries;
+            manifest.databases.push(ManifestEntry { database: db, file, entries });
+        }
+
+        let manifest_path = dir_path.join(MANIFEST_FILE);
+        let json = Self::to_json(&manifest, pretty)
+            .context("Failed to serialize backup manifest to JSON")?;
+        fs::write(&manifest_path, json)
+            .with_context(|| format!("Failed to write backup manifest to {}", manifest_path.display()))?;
+
+        println!("{} Backed up {} entries across {} files to {}",
+            ColoredOutput::success("Success:"),
+            ColoredOutput::count(total_entries),
+            ColoredOutput::count(manifest.databases.len()),
+            ColoredOutput::path(dir)
+        );
         Ok(())
     }
 
     /// Restores entries from a JSON backup file.
     /// 
+    /// Every key that is successfully set is appended to a journal under
+    /// `~/.skatos/journals`. If an entry fails, restore stops and keeps the journal
+    /// so a later run with `resume` skips the keys already applied. The journal is
+    /// removed once the whole file has been restored.
+    /// 
     /// # Arguments
     /// 
     /// * `input_path` - The path to the backup file to restore from
+    /// * `database` - Optional database name (defaults to "default")
+    /// * `on_conflict` - What to do when a key already exists in the database
+    /// * `resume` - Skip keys recorded in the journal by a previous, interrupted run
     /// 
     /// # Returns
     /// 
-    /// Returns `Ok(())` on success, or an error if reading file or setting entries fails.
-    pub fn restore_from_file(storage: &Storage, input_path: &str) -> Result<()> {
+    /// Returns the number of entries restored, or an error if reading the file or setting an entry fails.
+    pub fn restore_from_file(
+        storage: &Storage,
+        input_path: &str,
+        database: Option<&str>,
+        on_conflict: ConflictStrategy,
+        resume: bool,
+    ) -> Result<usize> {
+        let entries = Self::read_backup(input_path)?;
+        Self::restore_entries(storage, entries, input_path, database, on_conflict, resume)
+    }
+
+    /// Reads and parses a JSON backup file.
+    fn read_backup(input_path: &str) -> Result<Vec<SkateEntry>> {
         let content = fs::read_to_string(input_path)
             .with_context(|| format!("Failed to read backup file from {}", input_path))?;
 
-        let entries: Vec<SkateEntry> = serde_json::from_str(&content)
-            .context("Failed to parse backup file as JSON")?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse backup file {} as JSON", input_path))
+    }
+
+    /// Applies parsed backup entries to a database, journaling progress for `input_path`.
+    fn restore_entries(
+        storage: &Storage,
+        entries: Vec<SkateEntry>,
+        input_path: &str,
+        database: Option<&str>,
+        on_conflict: ConflictStrategy,
+        resume: bool,
+    ) -> Result<usize> {
+        let journal_path = Self::journal_path(storage, input_path, database.unwrap_or("default"));
+        let applied = if resume {
+            Self::read_journal(&journal_path)?
+        } else {
+            if journal_path.exists() {
+                fs::remove_file(&journal_path)
+                    .with_context(|| format!("Failed to remove restore journal {}", journal_path.display()))?;
+            }
+            HashSet::new()
+        };
+        let mut journal = None;
 
         let mut restored = 0;
+        let mut skipped = 0;
+        let mut resumed = 0;
         for entry in entries {
-            if storage.set(&entry.key, &entry.value, None).is_ok() {
-                restored += 1;
+            if applied.contains(&entry.key) {
+                resumed += 1;
+                continue;
+            }
+            if on_conflict == ConflictStrategy::Skip && storage.get(&entry.key, database)?.is_some() {
+                skipped += 1;
+                continue;
+            }
+
+            // Restored values were already accepted once, so they skip the size limit.
+            let options = SetOptions { force: true, expires_at: entry.expires_at };
+            storage.set(&entry.key, &entry.value, database, &options).with_context(|| {
+                format!(
+                    "Failed to restore '{}'; the {} entries applied before it are journaled and \
+                     skipped by --resume once the cause is fixed",
+                    entry.key,
+                    applied.len() + restored
+                )
+            })?;
+
+            if journal.is_none() {
+                journal = Some(Self::open_journal(&journal_path)?);
             }
+            if let Some(file) = journal.as_mut() {
+                writeln!(file, "{}", serde_json::to_string(&entry.key)?)
+                    .with_context(|| format!("Failed to write restore journal {}", journal_path.display()))?;
+            }
+            restored += 1;
+        }
+
+        drop(journal);
+        if journal_path.exists() {
+            fs::remove_file(&journal_path)
+                .with_context(|| format!("Failed to remove restore journal {}", journal_path.display()))?;
         }
 
-        println!("{} Restored {} entries from {}",
+        println!("{} Restored {} entries into {} from {} ({} skipped, {} already applied)",
             ColoredOutput::success("Success:"),
             ColoredOutput::count(restored),
-            ColoredOutput::path(input_path)
+            ColoredOutput::database(database.unwrap_or("default")),
+            ColoredOutput::path(input_path),
+            ColoredOutput::count(skipped),
+            ColoredOutput::count(resumed)
+        );
+        Ok(restored)
+    }
+
+    /// Path of the restore journal for a backup file and target database.
+    ///
+    /// Journals live under `~/.skatos/journals` rather than next to the backup,
+    /// so backups in read-only locations can still be restored. The file name is
+    /// a fixed-length hash of the database and the canonical backup path, so long
+    /// paths stay within file name limits and distinct paths don't collide.
+    fn journal_path(storage: &Storage, input_path: &str, database: &str) -> PathBuf {
+        let absolute = fs::canonicalize(input_path).unwrap_or_else(|_| PathBuf::from(input_path));
+        let hash = Self::fnv1a(&[database.as_bytes(), b"\0", absolute.as_os_str().as_encoded_bytes()]);
+        storage.journal_dir().join(format!("{:016x}.journal", hash))
+    }
+
+    /// 64-bit FNV-1a over the concatenated byte slices. Unlike `DefaultHasher`,
+    /// the result is stable across Rust releases, so journals survive upgrades.
+    fn fnv1a(parts: &[&[u8]]) -> u64 {
+        parts.iter().flat_map(|part| part.iter()).fold(0xcbf29ce484222325, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        })
+    }
+
+    /// Opens a restore journal for appending, creating it and its directory if needed.
+    fn open_journal(journal_path: &Path) -> Result<fs::File> {
+        if let Some(dir) = journal_path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create journal directory {}", dir.display()))?;
+        }
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(journal_path)
+            .with_context(|| format!("Failed to open restore journal {}", journal_path.display()))
+    }
+
+    /// Reads the keys recorded in a restore journal, if one exists.
+    fn read_journal(journal_path: &Path) -> Result<HashSet<String>> {
+        if !journal_path.exists() {
+            return Ok(HashSet::new());
+        }
+
+        let content = fs::read_to_string(journal_path)
+            .with_context(|| format!("Failed to read restore journal {}", journal_path.display()))?;
+
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line)
+                    .with_context(|| format!("Failed to parse restore journal {}", journal_path.display()))
+            })
+            .collect()
+    }
+
+    /// Restores every `<db>.json` file in a directory into its database.
+    ///
+    /// When a `manifest.json` written by `backup-all` is present, it defines
+    /// which files are restored and in what order; missing files and names with
+    /// path separators are errors, and entry count mismatches are reported as warnings. Without a manifest,
+    /// all `.json` files in the directory are restored in name order.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory containing the backup files
+    /// * `on_conflict` - What to do when a key already exists in a database
+    /// * `resume` - Skip keys recorded in each file's journal by a previous, interrupted run
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error if reading the directory or any backup fails.
+    pub fn restore_from_dir(
+        storage: &Storage,
+        dir: &str,
+        on_conflict: ConflictStrategy,
+        resume: bool,
+    ) -> Result<()> {
+        let dir_path = Path::new(dir);
+        let manifest_path = dir_path.join(MANIFEST_FILE);
+
+        let has_manifest = manifest_path.exists();
+
+        let manifest = if has_manifest {
+            let content = fs::read_to_string(&manifest_path)
+                .with_context(|| format!("Failed to read backup manifest from {}", manifest_path.display()))?;
+            serde_json::from_str::<BackupManifest>(&content)
+                .context("Failed to parse backup manifest as JSON")?
+        } else {
+            Self::scan_backup_dir(dir_path)?
+        };
+
+        let mut total_restored = 0;
+        for item in &manifest.databases {
+            if !Self::is_plain_name(&item.database) || !Self::is_plain_name(&item.file) {
+                anyhow::bail!(
+                    "Manifest entry '{}' ({}) must not contain path separators",
+                    item.database,
+                    item.file
+                );
+            }
+
+            let input_path = dir_path.join(&item.file);
+            if !input_path.exists() {
+                anyhow::bail!("Backup file {} listed in manifest is missing", input_path.display());
+            }
+
+            let input_path = input_path.to_string_lossy();
+            let entries = Self::read_backup(&input_path)?;
+            if has_manifest && entries.len() != item.entries {
+                println!("{} {} contains {} entries, manifest expects {}",
+                    ColoredOutput::warning("Warning:"),
+                    ColoredOutput::path(&item.file),
+                    ColoredOutput::count(entries.len()),
+                    ColoredOutput::count(item.entries)
+                );
+            }
+
+            total_restored += Self::restore_entries(
+                storage,
+                entries,
+                &input_path,
+                Some(&item.database),
+                on_conflict,
+                resume,
+            )?;
+        }
+
+        println!("{} Restored {} entries across {} databases from {}",
+            ColoredOutput::success("Success:"),
+            ColoredOutput::count(total_restored),
+            ColoredOutput::count(manifest.databases.len()),
+            ColoredOutput::path(dir)
         );
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Returns whether a manifest name is a single path component that stays inside its directory.
+    fn is_plain_name(name: &str) -> bool {
+        !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\'])
+    }
+
+    /// Builds a manifest from the `.json` files in a directory, sorted by name.
+    fn scan_backup_dir(dir_path: &Path) -> Result<BackupManifest> {
+        let entries = fs::read_dir(dir_path)
+            .with_context(|| format!("Failed to read backup directory {}", dir_path.display()))?;
+
+        let mut databases = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if !path.is_file() || path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            if let (Some(stem), Some(file)) = (path.file_stem(), path.file_name()) {
+                databases.push(ManifestEntry {
+                    database: stem.to_string_lossy().to_string(),
+                    file: file.to_string_lossy().to_string(),
+                    entries: 0,
+                });
+            }
+        }
+
+        databases.sort_by(|a, b| a.database.cmp(&b.database));
+        Ok(BackupManifest { databases })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(key: &str, value: &str) -> SkateEntry {
+        SkateEntry {
+            key: key.to_string(),
+            value: value.to_string(),
+            expires_at: None,
+        }
+    }
+
+    #[test]
+    fn grouped_env_sorts_by_key() {
+        let entries = [entry("ab1", "w"), entry("ab", "y"), entry("db-port", "5"), entry("db-host", "h")];
+        assert_eq!(
+            EnvGenerator::entries_to_grouped_env_format(&entries),
+            "AB=y\nAB1=w\n\n# --- DB ---\nDB_HOST=h\nDB_PORT=5"
+        );
+    }
+
+    #[test]
+    fn properties_continue_multiline_values() {
+        let entries = [entry("cert", "a\nb\n  d")];
+        assert_eq!(
+            EnvGenerator::entries_to_properties_format(&entries),
+            "cert=a\\n\\\n    b\\n\\\n    \\  d"
+        );
+    }
+
+    #[test]
+    fn properties_escape_keys_and_non_ascii() {
+        let entries = [entry("a key=1", "caf\u{e9}\\")];
+        assert_eq!(
+            EnvGenerator::entries_to_properties_format(&entries),
+            "a\\ key\\=1=caf\\u00e9\\\\"
+        );
+    }
+
+    #[test]
+    fn null_format_keeps_multiline_values_intact() {
+        let entries = [entry("cert", "line1\nline2 'q' \"x\""), entry("db-host", "h")];
+        let output = EnvGenerator::entries_to_null_format(&entries);
+        let records: Vec<&str> = output.split_terminator('\0').collect();
+        assert_eq!(records, ["CERT=line1\nline2 'q' \"x\"", "DB_HOST=h"]);
+    }
+
+    /// Storage rooted in a fresh per-test temporary directory.
+    fn temp_storage(name: &str) -> (Storage, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("skatos-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        let storage = Storage::with_base_path(dir.join("store")).unwrap();
+        (storage, dir)
+    }
+
+    #[test]
+    fn journal_path_is_fixed_length_and_distinct() {
+        let (storage, dir) = temp_storage("journal-path");
+        let long = format!("/{}/backup.json", "d".repeat(400));
+        let paths = [
+            EnvGenerator::journal_path(&storage, "/a_b/x.json", "default"),
+            EnvGenerator::journal_path(&storage, "/a/b/x.json", "default"),
+            EnvGenerator::journal_path(&storage, "/a/b/x.json", "other"),
+            EnvGenerator::journal_path(&storage, &long, "default"),
+        ];
+
+        for path in &paths {
+            assert_eq!(path.parent(), Some(storage.journal_dir().as_path()));
+            assert_eq!(path.file_name().unwrap().len(), "0123456789abcdef.journal".len());
+        }
+        let unique: HashSet<_> = paths.iter().collect();
+        assert_eq!(unique.len(), paths.len());
+        assert_eq!(paths[1], EnvGenerator::journal_path(&storage, "/a/b/x.json", "default"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn read_journal_parses_keys_and_tolerates_missing_file() {
+        let (_, dir) = temp_storage("read-journal");
+        let journal = dir.join("restore.journal");
+        assert!(EnvGenerator::read_journal(&journal).unwrap().is_empty());
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&journal, "\"a\"\n\n\"with\\nnewline\"\n").unwrap();
+        let keys = EnvGenerator::read_journal(&journal).unwrap();
+        assert_eq!(keys, HashSet::from(["a".to_string(), "with\nnewline".to_string()]));
+
+        fs::write(&journal, "not json\n").unwrap();
+        assert!(EnvGenerator::read_journal(&journal).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn resume_skips_journaled_keys() {
+        let (storage, dir) = temp_storage("resume");
+        let backup = dir.join("backup.json");
+        fs::write(&backup, "[]").unwrap();
+        let backup = backup.to_string_lossy();
+
+        let journal = EnvGenerator::journal_path(&storage, &backup, "default");
+        let mut file = EnvGenerator::open_journal(&journal).unwrap();
+        writeln!(file, "\"done\"").unwrap();
+        drop(file);
+
+        let entries = vec![entry("done", "stale"), entry("todo", "fresh")];
+        let restored = EnvGenerator::restore_entries(
+            &storage, entries, &backup, None, ConflictStrategy::Overwrite, true,
+        ).unwrap();
+
+        assert_eq!(restored, 1);
+        assert_eq!(storage.get("done", None).unwrap(), None);
+        assert_eq!(storage.get("todo", None).unwrap().as_deref(), Some("fresh"));
+        assert!(!journal.exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn compose_file_keeps_values_as_strings() {
+        let entries = [
+            entry("flag", "yes"),
+            entry("debug", "off"),
+            entry("price", "$5 or ${HOME}"),
+            entry("cert", "line1\nline2"),
+        ];
+        let yaml = EnvGenerator::entries_to_compose_file(&entries, "web", "nginx").unwrap();
+        let parsed: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+
+        let environment: Vec<&str> = parsed["services"]["web"]["environment"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .map(|item| item.as_str().unwrap())
+            .collect();
+        assert_eq!(
+            environment,
+            ["CERT=line1\nline2", "DEBUG=off", "FLAG=yes", "PRICE=$$5 or $${HOME}"]
+        );
+        assert_eq!(parsed["services"]["web"]["image"].as_str(), Some("nginx"));
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::{Context, Result};
+use globset::{Glob, GlobMatcher};
+
+use crate::storage::SkateEntry;
+
+/// Selects keys by an optional prefix and an optional shell-glob pattern.
+///
+/// Glob patterns are matched against the original (untransformed) key:
+/// `*` matches any run of characters (including none) and `?` matches
+/// exactly one character. Both criteria must match when both are set.
+pub struct KeyFilter {
+    prefix: Option<String>,
+    glob: Option<GlobMatcher>,
+}
+
+impl KeyFilter {
+    /// Builds a filter from an optional prefix and an optional glob pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - Optional prefix the key must start with
+    /// * `glob` - Optional shell-glob pattern the key must match
+    ///
+    /// # Returns
+    ///
+    /// Returns the filter, or an error if the glob pattern is invalid.
+    pub fn new(prefix: Option<&str>, glob: Option<&str>) -> Result<Self> {
+        let glob = glob
+            .map(|pattern| {
+                Glob::new(pattern)
+                    .map(|g| g.compile_matcher())
+                    .with_context(|| format!("Invalid glob pattern: {}", pattern))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            prefix: prefix.map(|p| p.to_string()),
+            glob,
+        })
+    }
+
+    /// Returns whether the given key passes the filter.
+    pub fn matches(&self, key: &str) -> bool {
+        let prefix_ok = self.prefix.as_deref().is_none_or(|p| key.starts_with(p));
+        let glob_ok = self.glob.as_ref().is_none_or(|g| g.is_match(key));
+        prefix_ok && glob_ok
+    }
+
+    /// Keeps only the entries whose key passes the filter.
+    pub fn apply(&self, entries: Vec<SkateEntry>) -> Vec<SkateEntry> {
+        entries
+            .into_iter()
+            .filter(|entry| self.matches(&entry.key))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn star_matches_empty_run() {
+        let filter = KeyFilter::new(None, Some("db-*host")).unwrap();
+        assert!(filter.matches("db-host"));
+        assert!(filter.matches("db-main-host"));
+        assert!(!filter.matches("db-port"));
+    }
+
+    #[test]
+    fn question_mark_matches_exactly_one_character() {
+        let filter = KeyFilter::new(None, Some("db-?")).unwrap();
+        assert!(filter.matches("db-1"));
+        assert!(!filter.matches("db-"));
+        assert!(!filter.matches("db-12"));
+    }
+
+    #[test]
+    fn prefix_and_glob_must_both_match() {
+        let filter = KeyFilter::new(Some("app-"), Some("*-host")).unwrap();
+        assert!(filter.matches("app-db-host"));
+        assert!(!filter.matches("web-db-host"));
+        assert!(!filter.matches("app-db-port"));
+    }
+
+    #[test]
+    fn invalid_pattern_is_an_error() {
+        assert!(KeyFilter::new(None, Some("db-[")).is_err());
+    }
+}
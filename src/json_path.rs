@@ -0,0 +1,143 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+pub struct JsonPath;
+
+impl JsonPath {
+    /// Extracts a nested field from a JSON document.
+    ///
+    /// Paths are dotted field names with optional `[n]` array indices, and may
+    /// start with `$` or `.`: `.db.host`, `$.servers[0].port` and `db.host` are
+    /// all accepted. An empty path (or `.`) returns the whole document; empty
+    /// segments such as `.a..b` or a trailing `.` are rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - The JSON text to parse
+    /// * `path` - The path of the field to extract
+    ///
+    /// # Returns
+    ///
+    /// Returns the field as a string (strings unquoted, other values as compact JSON),
+    /// or an error if the text is not JSON or the path does not exist.
+    pub fn extract(json: &str, path: &str) -> Result<String> {
+        let document: Value = serde_json::from_str(json).context("Value is not valid JSON")?;
+
+        let mut current = &document;
+        for segment in Self::segments(path)? {
+            let next = match (&segment, current) {
+                (Segment::Field(name), Value::Object(map)) => map.get(name),
+                (Segment::Index(index), Value::Array(items)) => items.get(*index),
+                _ => None,
+            };
+            current = next.with_context(|| format!("Path '{}' not found", path))?;
+        }
+
+        Ok(match current {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+    }
+
+    /// Splits a path into field names and array indices.
+    fn segments(path: &str) -> Result<Vec<Segment>> {
+        let body = path.strip_prefix('$').unwrap_or(path);
+        let body = body.strip_prefix('.').unwrap_or(body);
+        let mut segments = Vec::new();
+        if body.is_empty() {
+            return Ok(segments);
+        }
+
+        for part in body.split('.') {
+            if part.is_empty() {
+                anyhow::bail!("Invalid path '{}': empty segment", path);
+            }
+
+            let (name, mut indices) = match part.find('[') {
+                Some(start) => (&part[..start], &part[start..]),
+                None => (part, ""),
+            };
+            if !name.is_empty() {
+                segments.push(Segment::Field(name.to_string()));
+            }
+
+            while let Some(rest) = indices.strip_prefix('[') {
+                let end = rest
+                    .find(']')
+                    .with_context(|| format!("Invalid path '{}': unclosed '['", path))?;
+                let index = rest[..end]
+                    .parse()
+                    .with_context(|| format!("Invalid path '{}': array index must be a number", path))?;
+                segments.push(Segment::Index(index));
+                indices = &rest[end + 1..];
+            }
+            if !indices.is_empty() {
+                anyhow::bail!("Invalid path '{}': unexpected '{}'", path, indices);
+            }
+        }
+
+        Ok(segments)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Field(String),
+    Index(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str) -> Segment {
+        Segment::Field(name.to_string())
+    }
+
+    #[test]
+    fn segments_accept_leading_dot_and_dollar() {
+        let expected = [field("db"), field("host")];
+        for path in ["db.host", ".db.host", "$.db.host", "$db.host"] {
+            assert_eq!(JsonPath::segments(path).unwrap(), expected, "{}", path);
+        }
+        for path in ["", ".", "$", "$."] {
+            assert!(JsonPath::segments(path).unwrap().is_empty(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn segments_parse_indices() {
+        assert_eq!(
+            JsonPath::segments(".servers[0].ports[1][2]").unwrap(),
+            [field("servers"), Segment::Index(0), field("ports"), Segment::Index(1), Segment::Index(2)]
+        );
+        assert_eq!(JsonPath::segments("$[3]").unwrap(), [Segment::Index(3)]);
+    }
+
+    #[test]
+    fn segments_reject_malformed_paths() {
+        for path in [".a..b", "a.", "..a", ".a[", ".a[x]", ".a[-1]", ".a[0]b"] {
+            assert!(JsonPath::segments(path).is_err(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn extract_returns_strings_unquoted_and_other_values_as_json() {
+        let json = r#"{"db":{"host":"localhost","port":5432},"servers":[{"port":80},{"port":443}],"tags":["a"]}"#;
+        assert_eq!(JsonPath::extract(json, ".db.host").unwrap(), "localhost");
+        assert_eq!(JsonPath::extract(json, "$.db.port").unwrap(), "5432");
+        assert_eq!(JsonPath::extract(json, ".servers[1].port").unwrap(), "443");
+        assert_eq!(JsonPath::extract(json, ".tags").unwrap(), r#"["a"]"#);
+        assert_eq!(JsonPath::extract(r#"{"a":1}"#, ".").unwrap(), r#"{"a":1}"#);
+    }
+
+    #[test]
+    fn extract_fails_on_missing_paths_and_non_json() {
+        let json = r#"{"db":{"host":"localhost"},"servers":[{"port":80}]}"#;
+        for path in [".db.user", ".servers[5]", ".db[0]", ".servers.port", ".db.host.x"] {
+            let err = JsonPath::extract(json, path).unwrap_err();
+            assert!(err.to_string().contains("not found"), "{}: {}", path, err);
+        }
+        assert!(JsonPath::extract("plain text", ".a").is_err());
+    }
+}
@@ -1,23 +1,62 @@
 mod storage;
 mod env_gen;
 mod colors;
+mod filter;
+mod dotenv;
+mod json_path;
+#[cfg(unix)]
+mod server;
 
-use anyhow::Result;
-use clap::{CommandFactory, Parser, Subcommand};
+use anyhow::{Context, Result};
+use clap::{Args, CommandFactory, Parser, Subcommand};
 use clap_complete::{generate, Shell};
-use env_gen::EnvGenerator;
-use storage::Storage;
+use std::time::Duration;
+use env_gen::{ConflictStrategy, EnvFormat, EnvGenerator, EnvOptions, ExportFormat};
+use storage::{format_size, SetOptions, SizeLimits, Storage};
 use colors::ColoredOutput;
+use filter::KeyFilter;
+use dotenv::{DotenvParser, EnvDiff};
+use json_path::JsonPath;
+#[cfg(unix)]
+use server::Server;
 
 #[derive(Parser)]
 #[command(name = "skatos")]
 #[command(about = "🛹 Generate environment files from skatos variables")]
 #[command(version = "0.1.0")]
 struct Cli {
+    #[arg(long, global = true, help = "Treat expired keys as present instead of absent")]
+    include_expired: bool,
     #[command(subcommand)]
     command: Commands,
 }
 
+#[derive(Args)]
+struct JsonStyle {
+    #[arg(long, conflicts_with = "compact", help = "Pretty-print JSON output")]
+    pretty: bool,
+    #[arg(long, help = "Minify JSON output")]
+    compact: bool,
+}
+
+impl JsonStyle {
+    /// Resolves the flags to whether output should be pretty-printed.
+    fn is_pretty(&self, default: bool) -> bool {
+        if self.pretty {
+            true
+        } else if self.compact {
+            false
+        } else {
+            default
+        }
+    }
+
+    /// Returns whether either flag was given.
+    fn is_set(&self) -> bool {
+        self.pretty || self.compact
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     #[command(about = "Generate .env file from skatos variables")]
@@ -26,6 +65,18 @@ enum Commands {
         output: String,
         #[arg(short, long, help = "Filter keys by prefix")]
         filter: Option<String>,
+        #[arg(short, long, help = "Filter keys by glob pattern (* = any run of characters, ? = one character)")]
+        glob: Option<String>,
+        #[arg(long, value_enum, default_value = "dotenv", help = "Output format")]
+        format: EnvFormat,
+        #[arg(long, help = "Group variables under '# --- PREFIX ---' comment headers (dotenv only)")]
+        group_by_prefix: bool,
+        #[command(flatten)]
+        json_style: JsonStyle,
+        #[arg(long, help = "Service name (compose-file only, default: app)")]
+        service: Option<String>,
+        #[arg(long, required_if_eq("format", "compose-file"), help = "Service image (compose-file only)")]
+        image: Option<String>,
     },
     #[command(about = "Generate .env file from specific database")]
     EnvFromDb {
@@ -45,6 +96,17 @@ enum Commands {
         database: Option<String>,
         #[arg(short, long, help = "Filter keys by prefix")]
         filter: Option<String>,
+        #[arg(short, long, help = "Filter keys by glob pattern (* = any run of characters, ? = one character)")]
+        glob: Option<String>,
+        #[arg(long, value_enum, default_value = "shell", help = "Output format")]
+        format: ExportFormat,
+    },
+    #[command(about = "Compare two dotenv files (exit 1 on differences, 2 on errors)")]
+    CompareEnv {
+        #[arg(help = "Base dotenv file")]
+        old: String,
+        #[arg(help = "Dotenv file to compare against the base")]
+        new: String,
     },
     #[command(about = "Set a key-value pair")]
     Set {
@@ -52,35 +114,100 @@ enum Commands {
         key: String,
         #[arg(help = "Value")]
         value: String,
+        #[arg(long, help = "Store the value even if it exceeds the size limit")]
+        force: bool,
+        #[arg(long, value_parser = parse_ttl, help = "Expire the key after this duration (e.g. 30m, 1h, 7d)")]
+        ttl: Option<Duration>,
     },
     #[command(about = "Get a value")]
     Get {
         #[arg(help = "Key name")]
         key: String,
+        #[arg(long, help = "Extract a field from a JSON value (e.g. .db.host or .servers[0].port)")]
+        json_path: Option<String>,
     },
     #[command(about = "List all entries")]
-    List,
+    List {
+        #[arg(short, long, help = "Filter keys by glob pattern (* = any run of characters, ? = one character)")]
+        glob: Option<String>,
+        #[arg(long, help = "Output entries as JSON (compact unless --pretty)")]
+        json: bool,
+        #[command(flatten)]
+        json_style: JsonStyle,
+    },
     #[command(about = "List all keys")]
     Keys,
     #[command(about = "List all databases")]
     Dbs,
-    #[command(about = "Delete a key")]
-    Delete {
+    #[command(about = "Find which databases contain a key")]
+    WhichDb {
         #[arg(help = "Key name")]
         key: String,
+        #[arg(short, long, help = "Show the value stored in each database")]
+        show_values: bool,
     },
+    #[command(about = "Delete a key, or every key matching --glob")]
+    Delete {
+        #[arg(help = "Key name", required_unless_present = "glob", conflicts_with = "glob")]
+        key: Option<String>,
+        #[arg(short, long, help = "Filter keys by glob pattern (* = any run of characters, ? = one character)")]
+        glob: Option<String>,
+    },
+    #[command(about = "Delete expired keys from every database")]
+    Prune,
     #[command(about = "Backup all data to JSON file")]
     Backup {
         #[arg(short, long, default_value = "skatos_backup.json")]
         output: String,
+        #[command(flatten)]
+        json_style: JsonStyle,
+    },
+    #[command(about = "Backup every database to separate JSON files in a directory")]
+    BackupAll {
+        #[arg(short, long, default_value = "skatos_backups")]
+        dir: String,
+        #[command(flatten)]
+        json_style: JsonStyle,
     },
     #[command(about = "Restore data from JSON file")]
     Restore {
         #[arg(help = "Input JSON file path")]
         input: String,
+        #[arg(long, value_enum, default_value = "overwrite", help = "How to handle keys that already exist")]
+        on_conflict: ConflictStrategy,
+        #[arg(long, help = "Skip keys already applied by a previous, interrupted restore")]
+        resume: bool,
+    },
+    #[command(about = "Restore every <db>.json file in a directory into its database")]
+    RestoreDir {
+        #[arg(help = "Directory containing backup files")]
+        dir: String,
+        #[arg(long, value_enum, default_value = "overwrite", help = "How to handle keys that already exist")]
+        on_conflict: ConflictStrategy,
+        #[arg(long, help = "Skip keys already applied by a previous, interrupted restore")]
+        resume: bool,
     },
     #[command(about = "Import data from original skate (requires skate CLI)")]
     Import,
+    #[command(about = "Import variables from the current process environment")]
+    ImportFromEnv {
+        #[arg(short, long, help = "Only import variables starting with this prefix")]
+        filter: String,
+        #[arg(short, long, help = "Strip the prefix and lowercase stored keys (MYAPP_PORT -> port)")]
+        strip_prefix: bool,
+        #[arg(short, long, help = "Store keys in lowercase (implied by --strip-prefix)")]
+        lowercase: bool,
+        #[arg(short, long, help = "Database name (default: default)")]
+        database: Option<String>,
+    },
+    #[cfg(unix)]
+    #[command(about = "Serve read-only lookups (GET <key>) over a Unix domain socket")]
+    Serve {
+        #[arg(short, long, default_value = "/tmp/skatos.sock")]
+        socket: String,
+        #[arg(short, long, help = "Database name (default: default)")]
+        database: Option<String>,
+    },
     #[command(about = "Generate shell completions")]
     Completions {
         #[arg(help = "Shell type (bash, zsh, fish, elvish, powershell)")]
@@ -88,6 +215,15 @@ enum Commands {
     },
 }
 
+/// Parses a `--ttl` duration, rejecting anything shorter than one second.
+fn parse_ttl(raw: &str) -> Result<Duration, String> {
+    let ttl = humantime::parse_duration(raw).map_err(|e| e.to_string())?;
+    if ttl < Duration::from_secs(1) {
+        return Err("TTL must be at least 1s".to_string());
+    }
+    Ok(ttl)
+}
+
 /// Entry point for the skatos CLI application.
 ///
 /// Parses command line arguments and executes the appropriate operation
@@ -99,40 +235,127 @@ enum Commands {
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
-    let storage = Storage::new()?;
+    let storage = Storage::new()?.include_expired(cli.include_expired);
 
     match cli.command {
-        Commands::Env { output, filter } => {
-            EnvGenerator::generate_env_file(&storage, &output, filter.as_deref())?;
+        Commands::Env { output, filter, glob, format, group_by_prefix, json_style, service, image } => {
+            if json_style.is_set() && format != EnvFormat::Json {
+                anyhow::bail!("--pretty and --compact are only supported with --format json");
+            }
+            let key_filter = KeyFilter::new(filter.as_deref(), glob.as_deref())?;
+            let options = EnvOptions {
+                format,
+                group_by_prefix,
+                pretty: json_style.is_pretty(false),
+                service,
+                image,
+            };
+            EnvGenerator::generate_env_file(&storage, &output, &key_filter, &options)?;
         }
         Commands::EnvFromDb { database, output } => {
             EnvGenerator::generate_from_db(&storage, &database, &output)?;
         }
         Commands::Preview { filter } => {
-            EnvGenerator::show_preview(&storage, filter.as_deref())?;
+            let key_filter = KeyFilter::new(filter.as_deref(), None)?;
+            EnvGenerator::show_preview(&storage, &key_filter)?;
         }
-        Commands::Export { database, filter } => {
-            EnvGenerator::export_shell(&storage, database.as_deref(), filter.as_deref())?;
+        Commands::Export { database, filter, glob, format } => {
+            let key_filter = KeyFilter::new(filter.as_deref(), glob.as_deref())?;
+            EnvGenerator::export_shell(&storage, database.as_deref(), &key_filter, format)?;
         }
-        Commands::Set { key, value } => {
-            storage.set(&key, &value, None)?;
+        Commands::CompareEnv { old, new } => {
+            // Follow diff(1): exit 1 on differences, 2 when a file can't be read or parsed.
+            let parsed = DotenvParser::parse_file(&old)
+                .and_then(|old_vars| Ok((old_vars, DotenvParser::parse_file(&new)?)));
+            let (old_vars, new_vars) = match parsed {
+                Ok(vars) => vars,
+                Err(e) => {
+                    eprintln!("{} {:#}", ColoredOutput::error("Error:"), e);
+                    std::process::exit(2);
+                }
+            };
+            let diff = EnvDiff::between(&old_vars, &new_vars);
+            if diff.is_empty() {
+                println!("{} {} and {} are identical",
+                    ColoredOutput::success("Success:"),
+                    ColoredOutput::path(&old),
+                    ColoredOutput::path(&new)
+                );
+            } else {
+                for (key, value) in &diff.added {
+                    println!("{} {}", ColoredOutput::success("+"), ColoredOutput::format_key_value(key, value));
+                }
+                for (key, value) in &diff.removed {
+                    println!("{} {}", ColoredOutput::error("-"), ColoredOutput::format_key_value(key, value));
+                }
+                for (key, old_value, new_value) in &diff.changed {
+                    println!("{} {} {} -> {}",
+                        ColoredOutput::warning("~"),
+                        ColoredOutput::key(key),
+                        ColoredOutput::value(old_value),
+                        ColoredOutput::value(new_value)
+                    );
+                }
+                println!("{} {} added, {} removed, {} changed",
+                    ColoredOutput::info("Info:"),
+                    ColoredOutput::count(diff.added.len()),
+                    ColoredOutput::count(diff.removed.len()),
+                    ColoredOutput::count(diff.changed.len())
+                );
+                std::process::exit(1);
+            }
+        }
+        Commands::Set { key, value, force, ttl } => {
+            let mut options = SetOptions { force, ..SetOptions::default() };
+            if let Some(ttl) = ttl {
+                options = options.with_ttl(ttl);
+            }
+            storage.set(&key, &value, None, &options)?;
+            let limits = SizeLimits::from_env()?;
+            if value.len() > limits.warn_bytes {
+                eprintln!("{} Value for '{}' is {} (warning threshold {})",
+                    ColoredOutput::warning("Warning:"),
+                    ColoredOutput::key(&key),
+                    format_size(value.len()),
+                    format_size(limits.warn_bytes)
+                );
+            }
             println!("{} {}",
                 ColoredOutput::success("Set"),
                 ColoredOutput::format_key_value(&key, &value)
             );
+            if let Some(ttl) = ttl {
+                println!("{} Expires in {}",
+                    ColoredOutput::info("Info:"),
+                    humantime::format_duration(ttl)
+                );
+            }
         }
-        Commands::Get { key } => {
+        Commands::Get { key, json_path } => {
             match storage.get(&key, None)? {
-                Some(value) => println!("{}", ColoredOutput::value(&value)),
+                Some(value) => match json_path {
+                    Some(path) => {
+                        let field = JsonPath::extract(&value, &path)
+                            .with_context(|| format!("Failed to extract '{}' from '{}'", path, key))?;
+                        println!("{}", ColoredOutput::value(&field));
+                    }
+                    None => println!("{}", ColoredOutput::value(&value)),
+                },
                 None => println!("{} Key '{}' not found",
                     ColoredOutput::error("Error:"),
                     ColoredOutput::key(&key)
                 ),
             }
         }
-        Commands::List => {
-            let entries = storage.list(None)?;
-            if entries.is_empty() {
+        Commands::List { glob, json, json_style } => {
+            if json_style.is_set() && !json {
+                anyhow::bail!("--pretty and --compact are only supported with --json");
+            }
+            let key_filter = KeyFilter::new(None, glob.as_deref())?;
+            let entries = key_filter.apply(storage.list(None)?);
+            if json {
+                println!("{}", EnvGenerator::to_json(&entries, json_style.is_pretty(false))?);
+            } else if entries.is_empty() {
                 println!("{}", ColoredOutput::warning("No entries found"));
             } else {
                 for entry in entries {
@@ -157,31 +380,90 @@ async fn main() -> Result<()> {
             } else {
                 println!("{}", ColoredOutput::header("Available databases:"));
                 for db in dbs {
-                    println!("  {} {}",
-                        "●".to_string(),
+                    println!("  ● {}",
                         ColoredOutput::database(&db)
                     );
                 }
             }
         }
-        Commands::Delete { key } => {
-            if storage.delete(&key, None)? {
-                println!("{} Deleted {}",
-                    ColoredOutput::success("Success:"),
-                    ColoredOutput::key(&key)
-                );
-            } else {
-                println!("{} Key '{}' not found",
+        Commands::WhichDb { key, show_values } => {
+            let mut found = 0;
+            for db in storage.list_databases()? {
+                if let Some(value) = storage.get(&key, Some(&db))? {
+                    found += 1;
+                    if show_values {
+                        println!("  ● {} {}",
+                            ColoredOutput::database(&db),
+                            ColoredOutput::format_key_value(&key, &value)
+                        );
+                    } else {
+                        println!("  ● {}", ColoredOutput::database(&db));
+                    }
+                }
+            }
+            if found == 0 {
+                println!("{} Key '{}' not found in any database",
                     ColoredOutput::error("Error:"),
                     ColoredOutput::key(&key)
                 );
+                std::process::exit(1);
             }
         }
-        Commands::Backup { output } => {
-            EnvGenerator::backup_to_file(&storage, &output)?;
+        Commands::Delete { key, glob } => {
+            if let Some(pattern) = glob {
+                let key_filter = KeyFilter::new(None, Some(&pattern))?;
+                let mut deleted = 0;
+                for key in storage.list_keys(None)? {
+                    if key_filter.matches(&key) && storage.delete(&key, None)? {
+                        deleted += 1;
+                    }
+                }
+                println!("{} Deleted {} keys matching {}",
+                    ColoredOutput::success("Success:"),
+                    ColoredOutput::count(deleted),
+                    ColoredOutput::key(&pattern)
+                );
+            } else if let Some(key) = key {
+                if storage.delete(&key, None)? {
+                    println!("{} Deleted {}",
+                        ColoredOutput::success("Success:"),
+                        ColoredOutput::key(&key)
+                    );
+                } else {
+                    println!("{} Key '{}' not found",
+                        ColoredOutput::error("Error:"),
+                        ColoredOutput::key(&key)
+                    );
+                }
+            }
+        }
+        Commands::Prune => {
+            let mut pruned = 0;
+            for db in storage.list_databases()? {
+                for key in storage.prune_expired(Some(&db))? {
+                    println!("  ● {} {}",
+                        ColoredOutput::database(&db),
+                        ColoredOutput::key(&key)
+                    );
+                    pruned += 1;
+                }
+            }
+            println!("{} Pruned {} expired keys",
+                ColoredOutput::success("Success:"),
+                ColoredOutput::count(pruned)
+            );
         }
-        Commands::Restore { input } => {
-            EnvGenerator::restore_from_file(&storage, &input)?;
+        Commands::Backup { output, json_style } => {
+            EnvGenerator::backup_to_file(&storage, None, &output, json_style.is_pretty(true))?;
+        }
+        Commands::BackupAll { dir, json_style } => {
+            EnvGenerator::backup_all_to_dir(&storage, &dir, json_style.is_pretty(true))?;
+        }
+        Commands::Restore { input, on_conflict, resume } => {
+            EnvGenerator::restore_from_file(&storage, &input, None, on_conflict, resume)?;
+        }
+        Commands::RestoreDir { dir, on_conflict, resume } => {
+            EnvGenerator::restore_from_dir(&storage, &dir, on_conflict, resume)?;
         }
         Commands::Import => {
             println!("{}", ColoredOutput::info("Importing data from skate..."));
@@ -200,6 +482,24 @@ async fn main() -> Result<()> {
                 }
             }
         }
+        Commands::ImportFromEnv { filter, strip_prefix, lowercase, database } => {
+            let import = storage.import_from_env(&filter, strip_prefix, lowercase, database.as_deref())?;
+            for name in &import.skipped {
+                eprintln!("{} Skipped {}: name or value is not valid UTF-8",
+                    ColoredOutput::warning("Warning:"),
+                    ColoredOutput::key(name)
+                );
+            }
+            println!("{} Imported {} variables matching {}",
+                ColoredOutput::success("Success:"),
+                ColoredOutput::count(import.imported),
+                ColoredOutput::key(&filter)
+            );
+        }
+        #[cfg(unix)]
+        Commands::Serve { socket, database } => {
+            Server::serve(&storage, &socket, database.as_deref()).await?;
+        }
         Commands::Completions { shell } => {
             let mut cmd = Cli::command();
             generate(shell, &mut cmd, "skatos", &mut std::io::stdout());
@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::os::unix::fs::FileTypeExt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+
+use crate::colors::ColoredOutput;
+use crate::storage::Storage;
+
+/// Pause after a failed `accept` so a persistent error doesn't spin the loop.
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Read-only lookup server over a Unix domain socket.
+///
+/// Clients send one command per line. The only command is `GET <key>`, answered
+/// with `OK <value>` (backslashes and newlines in the value escaped as `\\` and
+/// `\n`), `NOT_FOUND`, or `ERR <message>`.
+pub struct Server;
+
+impl Server {
+    /// Serves lookups from a database until Ctrl-C is received.
+    ///
+    /// Failed accepts are reported and retried; only Ctrl-C stops the server.
+    ///
+    /// A stale socket file at `socket_path` is replaced; the socket is created
+    /// with owner-only permissions and removed again on shutdown.
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - The storage instance backing lookups
+    /// * `socket_path` - Path of the Unix domain socket to listen on
+    /// * `database` - Optional database name (defaults to "default")
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` after a graceful shutdown, or an error if the socket cannot be set up.
+    pub async fn serve(storage: &Storage, socket_path: &str, database: Option<&str>) -> Result<()> {
+        let path = Path::new(socket_path);
+        if let Ok(metadata) = fs::symlink_metadata(path) {
+            if !metadata.file_type().is_socket() {
+                anyhow::bail!("{} exists and is not a socket", socket_path);
+            }
+            fs::remove_file(path)
+                .with_context(|| format!("Failed to remove stale socket {}", socket_path))?;
+        }
+
+        // Create the socket owner-only from the start, so no other user can
+        // connect between bind and a later chmod.
+        let previous_umask = unsafe { libc::umask(0o177) };
+        let bound = UnixListener::bind(path);
+        unsafe { libc::umask(previous_umask) };
+        let listener = bound.with_context(|| format!("Failed to bind socket {}", socket_path))?;
+
+        println!("{} Serving {} on {} (Ctrl-C to stop)",
+            ColoredOutput::info("Info:"),
+            ColoredOutput::database(database.unwrap_or("default")),
+            ColoredOutput::path(socket_path)
+        );
+
+        let storage = Arc::new(storage.clone());
+        let database = Arc::new(database.map(|db| db.to_string()));
+
+        let shutdown = tokio::signal::ctrl_c();
+        tokio::pin!(shutdown);
+
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => {
+                    let stream = match accepted {
+                        Ok((stream, _)) => stream,
+                        Err(e) => {
+                            // Errors such as EMFILE or ECONNABORTED are usually transient:
+                            // report them, back off briefly and keep serving.
+                            eprintln!("{} Failed to accept connection: {}", ColoredOutput::error("Error:"), e);
+                            tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                            continue;
+                        }
+                    };
+                    let storage = Arc::clone(&storage);
+                    let database = Arc::clone(&database);
+                    tokio::spawn(async move {
+                        if let Err(e) = Self::handle_client(stream, storage, database).await {
+                            eprintln!("{} {}", ColoredOutput::error("Error:"), e);
+                        }
+                    });
+                }
+                _ = &mut shutdown => break,
+            }
+        }
+
+        fs::remove_file(path)
+            .with_context(|| format!("Failed to remove socket {}", socket_path))?;
+        println!("{} Server stopped", ColoredOutput::success("Success:"));
+        Ok(())
+    }
+
+    /// Answers commands from a single client until it disconnects.
+    ///
+    /// Lookups read the database file synchronously, so they run on the blocking
+    /// thread pool instead of stalling the async workers.
+    async fn handle_client(
+        stream: UnixStream,
+        storage: Arc<Storage>,
+        database: Arc<Option<String>>,
+    ) -> Result<()> {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+
+        while let Some(line) = lines.next_line().await? {
+            let response = match line.trim().split_once(' ') {
+                Some(("GET", key)) => {
+                    let storage = Arc::clone(&storage);
+                    let database = Arc::clone(&database);
+                    let key = key.trim().to_string();
+                    let lookup = tokio::task::spawn_blocking(move || storage.get(&key, database.as_deref())).await;
+
+                    match lookup {
+                        Ok(Ok(Some(value))) => format!("OK {}", value.replace('\\', "\\\\").replace('\n', "\\n")),
+                        Ok(Ok(None)) => "NOT_FOUND".to_string(),
+                        Ok(Err(e)) => format!("ERR {}", e),
+                        Err(e) => format!("ERR lookup failed: {}", e),
+                    }
+                }
+                _ => "ERR unsupported command, expected GET <key>".to_string(),
+            };
+
+            writer.write_all(response.as_bytes()).await?;
+            writer.write_all(b"\n").await?;
+        }
+
+        Ok(())
+    }
+}
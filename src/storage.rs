@@ -3,41 +3,154 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SkateEntry {
     pub key: String,
     pub value: String,
+    /// Expiry as a Unix timestamp in seconds, for keys set with a TTL.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Database {
     entries: HashMap<String, String>,
+    /// Expiry of keys set with a TTL, as Unix timestamps in seconds.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    expires: HashMap<String, u64>,
 }
 
 impl Database {
     fn new() -> Self {
         Self {
             entries: HashMap::new(),
+            expires: HashMap::new(),
         }
     }
+
+    fn is_expired(&self, key: &str, now: u64) -> bool {
+        self.expires.get(key).is_some_and(|&expires_at| expires_at <= now)
+    }
+}
+
+/// Current time as a Unix timestamp in seconds.
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Value size above which `set` prints a warning, unless overridden by `SKATOS_WARN_SIZE`.
+const DEFAULT_WARN_SIZE: usize = 64 * 1024;
+/// Value size above which `set` refuses to store, unless overridden by `SKATOS_MAX_SIZE`.
+const DEFAULT_MAX_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SizeLimits {
+    pub warn_bytes: usize,
+    pub max_bytes: usize,
+}
+
+impl SizeLimits {
+    /// Reads the limits from `SKATOS_WARN_SIZE` and `SKATOS_MAX_SIZE`, falling back to the defaults.
+    pub fn from_env() -> Result<Self> {
+        Ok(Self {
+            warn_bytes: Self::read_env("SKATOS_WARN_SIZE", DEFAULT_WARN_SIZE)?,
+            max_bytes: Self::read_env("SKATOS_MAX_SIZE", DEFAULT_MAX_SIZE)?,
+        })
+    }
+
+    /// Fails if `value` is above the hard limit.
+    fn check(&self, key: &str, value: &str) -> Result<()> {
+        if value.len() > self.max_bytes {
+            anyhow::bail!(
+                "Value for '{}' is {}, above the {} limit (use --force to store it anyway, or raise SKATOS_MAX_SIZE)",
+                key,
+                format_size(value.len()),
+                format_size(self.max_bytes)
+            );
+        }
+        Ok(())
+    }
+
+    fn read_env(name: &str, default: usize) -> Result<usize> {
+        match std::env::var(name) {
+            Ok(raw) => raw
+                .trim()
+                .parse()
+                .with_context(|| format!("{} must be a size in bytes, got '{}'", name, raw)),
+            Err(_) => Ok(default),
+        }
+    }
+}
+
+/// Options for `Storage::set`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SetOptions {
+    /// Store values above the hard size limit
+    pub force: bool,
+    /// Expiry as a Unix timestamp in seconds; `None` makes the key permanent
+    pub expires_at: Option<u64>,
 }
 
+impl SetOptions {
+    /// Expires the key `ttl` from now, rounded up to whole seconds.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        let ttl_secs = ttl.as_secs() + u64::from(ttl.subsec_nanos() > 0);
+        self.expires_at = Some(unix_now().saturating_add(ttl_secs));
+        self
+    }
+}
+
+/// Outcome of `import_from_env`.
+#[derive(Debug, Default)]
+pub struct EnvImport {
+    pub imported: usize,
+    /// Matching variables skipped because their name or value is not valid UTF-8.
+    pub skipped: Vec<String>,
+}
+
+#[derive(Clone)]
 pub struct Storage {
     base_path: PathBuf,
+    include_expired: bool,
 }
 
 impl Storage {
     pub fn new() -> Result<Self> {
         let home_dir = dirs::home_dir().context("Could not find home directory")?;
-        let base_path = home_dir.join(".skatos");
+        Self::with_base_path(home_dir.join(".skatos"))
+    }
 
+    /// Opens storage rooted at `base_path` instead of `~/.skatos`, creating it if needed.
+    pub fn with_base_path(base_path: PathBuf) -> Result<Self> {
         if !base_path.exists() {
             fs::create_dir_all(&base_path)
                 .context("Failed to create .skatos directory")?;
         }
 
-        Ok(Self { base_path })
+        Ok(Self {
+            base_path,
+            include_expired: false,
+        })
+    }
+
+    /// Makes `get` and the list methods return expired entries instead of treating them as absent.
+    pub fn include_expired(mut self, include_expired: bool) -> Self {
+        self.include_expired = include_expired;
+        self
+    }
+
+    fn is_hidden(&self, db: &Database, key: &str, now: u64) -> bool {
+        !self.include_expired && db.is_expired(key, now)
+    }
+
+    /// Directory holding restore journals; it is not created until a journal is written.
+    pub fn journal_dir(&self) -> PathBuf {
+        self.base_path.join("journals")
     }
 
     fn db_path(&self, database: &str) -> PathBuf {
@@ -71,11 +184,20 @@ impl Storage {
         Ok(())
     }
 
-    pub fn set(&self, key: &str, value: &str, database: Option<&str>) -> Result<()> {
+    /// Sets a key; the value and its expiry are written together.
+    pub fn set(&self, key: &str, value: &str, database: Option<&str>, options: &SetOptions) -> Result<()> {
+        if !options.force {
+            SizeLimits::from_env()?.check(key, value)?;
+        }
+
         let db_name = database.unwrap_or("default");
         let mut db = self.load_db(db_name)?;
 
         db.entries.insert(key.to_string(), value.to_string());
+        match options.expires_at {
+            Some(expires_at) => db.expires.insert(key.to_string(), expires_at),
+            None => db.expires.remove(key),
+        };
 
         self.save_db(db_name, &db)?;
         Ok(())
@@ -85,6 +207,10 @@ impl Storage {
         let db_name = database.unwrap_or("default");
         let db = self.load_db(db_name)?;
 
+        if self.is_hidden(&db, key, unix_now()) {
+            return Ok(None);
+        }
+
         Ok(db.entries.get(key).cloned())
     }
 
@@ -93,6 +219,7 @@ impl Storage {
         let mut db = self.load_db(db_name)?;
 
         let existed = db.entries.remove(key).is_some();
+        db.expires.remove(key);
 
         if existed {
             self.save_db(db_name, &db)?;
@@ -104,13 +231,16 @@ impl Storage {
     pub fn list(&self, database: Option<&str>) -> Result<Vec<SkateEntry>> {
         let db_name = database.unwrap_or("default");
         let db = self.load_db(db_name)?;
+        let now = unix_now();
 
         let mut entries: Vec<SkateEntry> = db
             .entries
             .iter()
+            .filter(|(k, _)| !self.is_hidden(&db, k, now))
             .map(|(k, v)| SkateEntry {
                 key: k.clone(),
                 value: v.clone(),
+                expires_at: db.expires.get(k).copied(),
             })
             .collect();
 
@@ -123,12 +253,45 @@ impl Storage {
         let db_name = database.unwrap_or("default");
         let db = self.load_db(db_name)?;
 
-        let mut keys: Vec<String> = db.entries.keys().cloned().collect();
+        let now = unix_now();
+
+        let mut keys: Vec<String> = db
+            .entries
+            .keys()
+            .filter(|k| !self.is_hidden(&db, k, now))
+            .cloned()
+            .collect();
         keys.sort();
 
         Ok(keys)
     }
 
+    /// Physically removes expired keys from a database, returning the removed keys.
+    pub fn prune_expired(&self, database: Option<&str>) -> Result<Vec<String>> {
+        let db_name = database.unwrap_or("default");
+        let mut db = self.load_db(db_name)?;
+        let now = unix_now();
+
+        let mut expired: Vec<String> = db
+            .expires
+            .keys()
+            .filter(|k| db.is_expired(k, now))
+            .cloned()
+            .collect();
+        expired.sort();
+
+        for key in &expired {
+            db.entries.remove(key);
+            db.expires.remove(key);
+        }
+
+        if !expired.is_empty() {
+            self.save_db(db_name, &db)?;
+        }
+
+        Ok(expired)
+    }
+
     pub fn list_databases(&self) -> Result<Vec<String>> {
         let entries = fs::read_dir(&self.base_path)
             .context("Failed to read .skatos directory")?;
@@ -166,6 +329,66 @@ impl Storage {
         Ok(())
     }
 
+    /// Imports process environment variables whose name starts with `prefix`.
+    ///
+    /// With `strip_prefix` the prefix is removed and the rest lowercased
+    /// (`MYAPP_PORT` -> `port`); `lowercase` lowercases keys that keep their
+    /// prefix. Matching variables whose name or value is not valid UTF-8 are
+    /// skipped and reported in the result. Values are subject to the same size
+    /// limit as `set`; nothing is written if any exceeds it.
+    pub fn import_from_env(
+        &self,
+        prefix: &str,
+        strip_prefix: bool,
+        lowercase: bool,
+        database: Option<&str>,
+    ) -> Result<EnvImport> {
+        let db_name = database.unwrap_or("default");
+        let mut db = self.load_db(db_name)?;
+        let mut result = EnvImport::default();
+        let limits = SizeLimits::from_env()?;
+
+        for (name, value) in std::env::vars_os() {
+            if !name.as_encoded_bytes().starts_with(prefix.as_bytes()) {
+                continue;
+            }
+            let (Some(name), Some(value)) = (name.to_str(), value.to_str()) else {
+                result.skipped.push(name.to_string_lossy().to_string());
+                continue;
+            };
+
+            let mut key = if strip_prefix {
+                name[prefix.len()..].to_string()
+            } else {
+                name.to_string()
+            };
+            if strip_prefix || lowercase {
+                key = key.to_lowercase();
+            }
+            if key.is_empty() {
+                continue;
+            }
+            if value.len() > limits.max_bytes {
+                anyhow::bail!(
+                    "{} is {}, above the {} limit (raise SKATOS_MAX_SIZE to import it); nothing was imported",
+                    name,
+                    format_size(value.len()),
+                    format_size(limits.max_bytes)
+                );
+            }
+
+            db.expires.remove(&key);
+            db.entries.insert(key, value.to_string());
+            result.imported += 1;
+        }
+
+        if result.imported > 0 {
+            self.save_db(db_name, &db)?;
+        }
+
+        Ok(result)
+    }
+
     pub fn import_from_skate(&self) -> Result<usize> {
         use std::process::Command;
 
@@ -217,6 +440,17 @@ impl Storage {
     }
 }
 
+/// Formats a byte count for humans, e.g. `70.0 KB`.
+pub fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
 impl Default for Storage {
     fn default() -> Self {
         Self::new().expect("Failed to initialize storage")